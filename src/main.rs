@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::PathBuf;
 use std::process;
 use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 use chrono::{prelude::*, Duration};
 use clap::{App, AppSettings, Arg, ArgMatches};
+use humantime::{format_duration, parse_duration};
 use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+use notify_rust::Notification;
 use serde::{Deserialize, Serialize};
 
 /// Default timer name.
@@ -15,6 +20,15 @@ const NAME_DEFAULT: &str = "main";
 /// Timers path.
 const TIMERS_PATH: &str = "peperone/timers.toml";
 
+/// Daemon socket path.
+const SOCKET_PATH: &str = "peperone/peperone.sock";
+
+/// Interval at which the daemon persists its in-memory state to disk.
+const DAEMON_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Interval at which the daemon pushes updates to subscribed tail clients.
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
 /// Main, start the program.
 fn main() {
     // Match CLI arguments
@@ -29,7 +43,10 @@ fn main() {
                     Arg::new("NAME")
                         .about("Timer name")
                         .default_value(NAME_DEFAULT),
-                ),
+                )
+                .arg(Arg::new("DURATION").about(
+                    "Target duration to count down to, human-friendly (e.g. 25m, 1h30m)",
+                )),
         )
         .subcommand(
             App::new("start")
@@ -70,7 +87,31 @@ fn main() {
                         .default_value(NAME_DEFAULT),
                 ),
         )
-        .subcommand(App::new("list").alias("ls").alias("l").about("List timers"))
+        .subcommand(
+            App::new("list")
+                .alias("ls")
+                .alias("l")
+                .about("List timers")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["plain", "json"])
+                        .default_value("plain")
+                        .about("Output format"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .about("Output as JSON, shorthand for --format json"),
+                )
+                .arg(
+                    Arg::new("template")
+                        .long("template")
+                        .takes_value(true)
+                        .about("Custom output template, e.g. '{name}: {elapsed}'"),
+                ),
+        )
         .subcommand(
             App::new("show")
                 .alias("cat")
@@ -88,6 +129,25 @@ fn main() {
                         .long("quiet")
                         .short('q')
                         .about("Quiet output"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["plain", "json"])
+                        .default_value("plain")
+                        .about("Output format"),
+                )
+                .arg(
+                    Arg::new("template")
+                        .long("template")
+                        .takes_value(true)
+                        .about("Custom output template, e.g. '{name}: {elapsed}'"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .about("Show all timers at once"),
                 ),
         )
         .subcommand(
@@ -109,8 +169,78 @@ fn main() {
                         .long("quiet")
                         .short('q')
                         .about("Quiet output"),
+                )
+                .arg(
+                    Arg::new("notify")
+                        .long("notify")
+                        .short('n')
+                        .about("Send a desktop notification when a countdown timer finishes"),
+                )
+                .arg(
+                    Arg::new("no-notify")
+                        .long("no-notify")
+                        .about("Do not send a desktop notification")
+                        .conflicts_with("notify"),
                 ),
         )
+        .subcommand(
+            App::new("pomodoro")
+                .alias("pomo")
+                .about("Manage pomodoros")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("start")
+                        .about("Start a new pomodoro")
+                        .arg(
+                            Arg::new("NAME")
+                                .about("Pomodoro name")
+                                .default_value(NAME_DEFAULT),
+                        )
+                        .arg(
+                            Arg::new("WORK")
+                                .about("Work interval duration")
+                                .default_value("25m"),
+                        )
+                        .arg(
+                            Arg::new("SHORT")
+                                .about("Short break duration")
+                                .default_value("5m"),
+                        )
+                        .arg(
+                            Arg::new("LONG")
+                                .about("Long break duration")
+                                .default_value("15m"),
+                        )
+                        .arg(
+                            Arg::new("COUNT")
+                                .about("Work intervals before a long break")
+                                .default_value("4"),
+                        ),
+                )
+                .subcommand(
+                    App::new("toggle")
+                        .alias("startstop")
+                        .about("Toggle pomodoro (start/stop)")
+                        .arg(
+                            Arg::new("NAME")
+                                .about("Pomodoro name")
+                                .default_value(NAME_DEFAULT),
+                        ),
+                )
+                .subcommand(
+                    App::new("stop")
+                        .about("Stop and remove pomodoro")
+                        .arg(
+                            Arg::new("NAME")
+                                .about("Pomodoro name")
+                                .default_value(NAME_DEFAULT),
+                        ),
+                ),
+        )
+        .subcommand(
+            App::new("daemon")
+                .about("Run as a daemon, serving timer state over a Unix socket"),
+        )
         .get_matches();
 
     // Load timers
@@ -133,6 +263,10 @@ fn main() {
         show(matcher, &mut timers);
     } else if let Some(matcher) = matches.subcommand_matches("tail") {
         tail(matcher, &mut timers);
+    } else if let Some(matcher) = matches.subcommand_matches("pomodoro") {
+        pomodoro(matcher, &mut timers);
+    } else if let Some(matcher) = matches.subcommand_matches("daemon") {
+        daemon(matcher, &mut timers);
     } else {
         unreachable!()
     }
@@ -142,6 +276,10 @@ fn main() {
 #[derive(Serialize, Deserialize, Debug, Default)]
 struct Timers {
     pub timers: HashMap<String, Timer>,
+
+    /// Running pomodoros.
+    #[serde(default)]
+    pub pomodoros: HashMap<String, Pomodoro>,
 }
 
 impl Timers {
@@ -173,7 +311,7 @@ impl Timers {
 }
 
 /// A timer.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 struct Timer {
     /// If active, last time we started counting at.
     #[serde(default)]
@@ -182,12 +320,19 @@ struct Timer {
     /// Additional elapsed time.
     #[serde(default)]
     offset: std::time::Duration,
+
+    /// Target duration to count down to, if this is a countdown timer.
+    #[serde(default)]
+    target: Option<std::time::Duration>,
 }
 
 impl Timer {
-    /// Create and start new timer.
-    pub fn new() -> Timer {
-        let mut timer = Timer::default();
+    /// Create and start new timer, optionally counting down to a target duration.
+    pub fn new(target: Option<std::time::Duration>) -> Timer {
+        let mut timer = Timer {
+            target,
+            ..Timer::default()
+        };
         timer.start();
         timer
     }
@@ -234,6 +379,228 @@ impl Timer {
             return format!("{}:{:02}", min, sec,);
         }
     }
+
+    /// Target duration as a `chrono::Duration`, if this is a countdown timer and the target
+    /// actually fits in `chrono::Duration`'s representable range.
+    fn target_duration(&self) -> Option<Duration> {
+        self.target.and_then(|target| Duration::from_std(target).ok())
+    }
+
+    /// Time remaining until the target duration, if this is a countdown timer.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.target_duration().map(|target| {
+            let remaining = target - self.elapsed();
+            remaining.max(Duration::zero())
+        })
+    }
+
+    /// Whether this is a countdown timer that reached its target duration.
+    pub fn finished(&self) -> bool {
+        match self.target_duration() {
+            Some(target) => self.elapsed() >= target,
+            None => false,
+        }
+    }
+
+    /// Format remaining time, if this is a countdown timer.
+    pub fn format_remaining(&self) -> Option<String> {
+        self.remaining().map(|remaining| {
+            let min = remaining.num_minutes() % 60;
+            let sec = remaining.num_seconds() % 60;
+
+            if remaining.num_hours() > 0 {
+                format!("{}:{:02}:{:02}", remaining.num_hours(), min, sec)
+            } else {
+                format!("{}:{:02}", min, sec)
+            }
+        })
+    }
+
+    /// Format timer for display, showing remaining time for countdown timers and elapsed time
+    /// otherwise.
+    pub fn format_display(&self) -> String {
+        match self.format_remaining() {
+            Some(remaining) if self.finished() => format!("{} (finished)", remaining),
+            Some(remaining) => remaining,
+            None => self.format_elapsed(),
+        }
+    }
+
+    /// Build a machine-readable snapshot of this timer, for `--format json`/`--template` output.
+    pub fn info<'a>(&self, name: &'a str) -> TimerInfo<'a> {
+        TimerInfo {
+            name,
+            running: self.running(),
+            elapsed: self.elapsed().num_seconds(),
+            target: self.target.map(|target| target.as_secs()),
+            remaining: self.remaining().map(|remaining| remaining.num_seconds()),
+            finished: self.finished(),
+        }
+    }
+}
+
+/// A machine-readable snapshot of a timer, for `--format json`/`--template` output.
+#[derive(Serialize, Debug)]
+struct TimerInfo<'a> {
+    name: &'a str,
+    running: bool,
+    elapsed: i64,
+    target: Option<u64>,
+    remaining: Option<i64>,
+    finished: bool,
+}
+
+/// Render a timer info into a custom output template.
+///
+/// Supports the placeholders `{name}`, `{running}`, `{elapsed}`, `{target}`, `{remaining}` and
+/// `{finished}`.
+fn render_template(template: &str, info: &TimerInfo) -> String {
+    template
+        .replace("{name}", info.name)
+        .replace("{running}", &info.running.to_string())
+        .replace("{elapsed}", &info.elapsed.to_string())
+        .replace(
+            "{target}",
+            &info.target.map(|target| target.to_string()).unwrap_or_default(),
+        )
+        .replace(
+            "{remaining}",
+            &info
+                .remaining
+                .map(|remaining| remaining.to_string())
+                .unwrap_or_default(),
+        )
+        .replace("{finished}", &info.finished.to_string())
+}
+
+/// A machine-readable snapshot of a pomodoro, for `--format json`/`--template` output.
+#[derive(Serialize, Debug)]
+struct PomodoroInfo<'a> {
+    #[serde(flatten)]
+    timer: TimerInfo<'a>,
+    phase: &'static str,
+    cycle: u32,
+}
+
+/// Render a pomodoro info into a custom output template.
+///
+/// Supports the same placeholders as `render_template`, plus `{phase}` and `{cycle}`.
+fn render_pomodoro_template(template: &str, info: &PomodoroInfo) -> String {
+    render_template(template, &info.timer)
+        .replace("{phase}", info.phase)
+        .replace("{cycle}", &info.cycle.to_string())
+}
+
+/// A pomodoro phase.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Focused work interval.
+    Work,
+
+    /// Short break between work intervals.
+    ShortBreak,
+
+    /// Long break after completing a set of work intervals.
+    LongBreak,
+}
+
+impl Default for Phase {
+    fn default() -> Self {
+        Phase::Work
+    }
+}
+
+impl Phase {
+    /// Human readable label, used for display and for the `phase` field of machine-readable output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Work => "work",
+            Phase::ShortBreak => "short break",
+            Phase::LongBreak => "long break",
+        }
+    }
+}
+
+/// A pomodoro, cycling through work and break intervals.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Pomodoro {
+    /// Current phase.
+    #[serde(default)]
+    phase: Phase,
+
+    /// Number of completed work intervals in the current set.
+    #[serde(default)]
+    cycle: u32,
+
+    /// Work interval duration.
+    work: std::time::Duration,
+
+    /// Short break duration.
+    short_break: std::time::Duration,
+
+    /// Long break duration.
+    long_break: std::time::Duration,
+
+    /// Number of work intervals before a long break.
+    count: u32,
+
+    /// Timer tracking the current phase.
+    timer: Timer,
+}
+
+impl Pomodoro {
+    /// Create and start a new pomodoro.
+    pub fn new(
+        work: std::time::Duration,
+        short_break: std::time::Duration,
+        long_break: std::time::Duration,
+        count: u32,
+    ) -> Pomodoro {
+        Pomodoro {
+            phase: Phase::default(),
+            cycle: 0,
+            work,
+            short_break,
+            long_break,
+            count,
+            timer: Timer::new(Some(work)),
+        }
+    }
+
+    /// Duration of the current phase.
+    pub fn phase_duration(&self) -> std::time::Duration {
+        match self.phase {
+            Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    /// Advance to the next phase, resetting the underlying timer.
+    pub fn advance(&mut self) {
+        self.phase = match self.phase {
+            Phase::Work if self.cycle + 1 >= self.count => {
+                self.cycle = 0;
+                Phase::LongBreak
+            }
+            Phase::Work => {
+                self.cycle += 1;
+                Phase::ShortBreak
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.timer = Timer::new(Some(self.phase_duration()));
+    }
+
+    /// Build a machine-readable snapshot of this pomodoro, for `--format json`/`--template`
+    /// output.
+    pub fn info<'a>(&self, name: &'a str) -> PomodoroInfo<'a> {
+        PomodoroInfo {
+            timer: self.timer.info(name),
+            phase: self.phase.label(),
+            cycle: self.cycle,
+        }
+    }
 }
 
 /// Get path to timers file.
@@ -244,16 +611,450 @@ fn timers_path() -> PathBuf {
         .into()
 }
 
+/// Get path to the daemon socket.
+fn daemon_path() -> PathBuf {
+    dirs::cache_dir()
+        .expect("cache dir cannot be found")
+        .join(SOCKET_PATH)
+        .into()
+}
+
+/// A command sent from a client to the daemon.
+#[derive(Serialize, Deserialize, Debug)]
+enum Command {
+    New {
+        name: String,
+        target: Option<std::time::Duration>,
+    },
+    Start {
+        name: String,
+    },
+    Stop {
+        name: String,
+    },
+    Toggle {
+        name: String,
+    },
+    Remove {
+        name: String,
+    },
+    List,
+    Show {
+        name: String,
+    },
+    Subscribe {
+        name: String,
+        keep_going: bool,
+    },
+    PomodoroStart {
+        name: String,
+        work: std::time::Duration,
+        short_break: std::time::Duration,
+        long_break: std::time::Duration,
+        count: u32,
+    },
+    PomodoroToggle {
+        name: String,
+    },
+    PomodoroStop {
+        name: String,
+    },
+}
+
+/// An answer sent from the daemon back to a client.
+#[derive(Serialize, Deserialize, Debug)]
+enum Answer {
+    /// Command applied successfully.
+    Ok,
+
+    /// Command failed, e.g. because the named timer doesn't exist.
+    Error(String),
+
+    /// Response to `Command::List`.
+    List(Vec<(String, Timer)>, Vec<(String, Pomodoro)>),
+
+    /// Response to `Command::Show`.
+    Timer(Timer),
+
+    /// Response to `Command::Show`, for a pomodoro.
+    Pomodoro(Pomodoro),
+
+    /// Pushed to a subscribed client on every tick or state change.
+    Tick(Timer),
+
+    /// Pushed to a subscribed client on every tick or state change, for a pomodoro subscription.
+    PomodoroTick(Pomodoro),
+
+    /// Pushed to a subscribed client once the timer it subscribed to is removed.
+    Removed,
+}
+
+/// Try to connect to the daemon.
+///
+/// Returns `None` if no daemon is running, in which case the caller should fall back to the
+/// file-based path.
+fn daemon_connect() -> Option<UnixStream> {
+    UnixStream::connect(daemon_path()).ok()
+}
+
+/// Send a command to the daemon and wait for its answer.
+///
+/// Returns `None` if no daemon is running, or if one was running but died mid-request (e.g. it
+/// panicked or was killed after accepting the connection) — callers fall back to the file-based
+/// path the same way as if there had never been a daemon to begin with.
+fn daemon_request(command: &Command) -> Option<Answer> {
+    let stream = daemon_connect()?;
+    serde_cbor::to_writer(&stream, command).ok()?;
+    // Read exactly one answer; `serde_cbor::from_reader` would also wait for end of stream, which
+    // never comes since the daemon keeps the connection open for further commands.
+    Answer::deserialize(&mut serde_cbor::Deserializer::from_reader(&stream)).ok()
+}
+
+/// Print the usual "no <kind> named" error and exit if the daemon answered with an error.
+fn exit_on_daemon_error(kind: &str, name: &str, answer: Answer) {
+    if let Answer::Error(_) = answer {
+        eprintln!("error: no {} named '{}'", kind, name);
+        process::exit(1);
+    }
+}
+
+/// Run as a daemon, serving timer state over a Unix socket.
+fn daemon(_matcher: &ArgMatches, timers: &mut Timers) {
+    let path = daemon_path();
+    if path.exists() {
+        if daemon_connect().is_some() {
+            eprintln!("error: a daemon is already running on {}", path.display());
+            process::exit(1);
+        }
+        fs::remove_file(&path).expect("failed to remove stale daemon socket");
+    }
+
+    let parent = path.parent().expect("failed to determine parent path");
+    fs::create_dir_all(parent).expect("failed to create parent directories for daemon socket");
+
+    let listener = UnixListener::bind(&path).expect("failed to bind daemon socket");
+    println!("daemon listening on {}", path.display());
+
+    let state = Arc::new(Mutex::new(std::mem::take(timers)));
+
+    // Periodically persist state to disk, so the daemon remains the single writer
+    {
+        let state = Arc::clone(&state);
+        thread::spawn(move || loop {
+            thread::sleep(DAEMON_SAVE_INTERVAL);
+            state.lock().unwrap().save();
+        });
+    }
+
+    // Periodically advance pomodoros whose current phase has finished. This has to happen here
+    // rather than in a tail client, since a pomodoro must keep progressing through its phases even
+    // when nothing is subscribed to it.
+    {
+        let state = Arc::clone(&state);
+        thread::spawn(move || loop {
+            thread::sleep(TICK_INTERVAL);
+            let mut timers = state.lock().unwrap();
+            for pomodoro in timers.pomodoros.values_mut() {
+                if pomodoro.timer.running() && pomodoro.timer.finished() {
+                    pomodoro.advance();
+                }
+            }
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let state = Arc::clone(&state);
+                thread::spawn(move || handle_daemon_connection(stream, state));
+            }
+            Err(err) => eprintln!("error: daemon failed to accept connection: {}", err),
+        }
+    }
+}
+
+/// Handle a single daemon client connection.
+fn handle_daemon_connection(stream: UnixStream, state: Arc<Mutex<Timers>>) {
+    for command in serde_cbor::Deserializer::from_reader(&stream).into_iter::<Command>() {
+        let command = match command {
+            Ok(command) => command,
+            Err(_) => return,
+        };
+
+        if let Command::Subscribe { name, keep_going } = command {
+            subscribe(&stream, &state, &name, keep_going);
+            return;
+        }
+
+        let answer = apply_command(&state, command);
+        if serde_cbor::to_writer(&stream, &answer).is_err() {
+            return;
+        }
+    }
+}
+
+/// Apply a command to the shared daemon state, returning the answer to send back.
+fn apply_command(state: &Arc<Mutex<Timers>>, command: Command) -> Answer {
+    let mut timers = state.lock().unwrap();
+    match command {
+        Command::New { name, target } => {
+            if timers.pomodoros.contains_key(&name) {
+                Answer::Error(format!("a pomodoro named '{}' already exists", name))
+            } else {
+                timers.timers.insert(name, Timer::new(target));
+                Answer::Ok
+            }
+        }
+        Command::Start { name } => match timers.timers.get_mut(&name) {
+            Some(timer) => {
+                timer.start();
+                Answer::Ok
+            }
+            None => Answer::Error(format!("no timer named '{}'", name)),
+        },
+        Command::Stop { name } => match timers.timers.get_mut(&name) {
+            Some(timer) => {
+                timer.stop();
+                Answer::Ok
+            }
+            None => Answer::Error(format!("no timer named '{}'", name)),
+        },
+        Command::Toggle { name } => match timers.timers.get_mut(&name) {
+            Some(timer) if timer.running() => {
+                timer.stop();
+                Answer::Ok
+            }
+            Some(timer) => {
+                timer.start();
+                Answer::Ok
+            }
+            None => Answer::Error(format!("no timer named '{}'", name)),
+        },
+        Command::Remove { name } => match timers.timers.remove(&name) {
+            Some(_) => Answer::Ok,
+            None => Answer::Error(format!("no timer named '{}'", name)),
+        },
+        Command::List => Answer::List(
+            timers
+                .timers
+                .iter()
+                .map(|(name, timer)| (name.clone(), timer.clone()))
+                .collect(),
+            timers
+                .pomodoros
+                .iter()
+                .map(|(name, pomodoro)| (name.clone(), pomodoro.clone()))
+                .collect(),
+        ),
+        Command::Show { name } => match timers.pomodoros.get(&name) {
+            Some(pomodoro) => Answer::Pomodoro(pomodoro.clone()),
+            None => match timers.timers.get(&name) {
+                Some(timer) => Answer::Timer(timer.clone()),
+                None => Answer::Error(format!("no timer named '{}'", name)),
+            },
+        },
+        Command::Subscribe { .. } => {
+            unreachable!("subscriptions are handled in handle_daemon_connection")
+        }
+        Command::PomodoroStart {
+            name,
+            work,
+            short_break,
+            long_break,
+            count,
+        } => {
+            if timers.timers.contains_key(&name) {
+                Answer::Error(format!("a timer named '{}' already exists", name))
+            } else {
+                timers
+                    .pomodoros
+                    .insert(name, Pomodoro::new(work, short_break, long_break, count));
+                Answer::Ok
+            }
+        }
+        Command::PomodoroToggle { name } => match timers.pomodoros.get_mut(&name) {
+            Some(pomodoro) if pomodoro.timer.running() => {
+                pomodoro.timer.stop();
+                Answer::Ok
+            }
+            Some(pomodoro) => {
+                pomodoro.timer.start();
+                Answer::Ok
+            }
+            None => Answer::Error(format!("no pomodoro named '{}'", name)),
+        },
+        Command::PomodoroStop { name } => match timers.pomodoros.remove(&name) {
+            Some(_) => Answer::Ok,
+            None => Answer::Error(format!("no pomodoro named '{}'", name)),
+        },
+    }
+}
+
+/// Whether the client on the other end of `stream` has gone away, checked via a non-blocking
+/// peek so callers that aren't otherwise writing to the stream (e.g. while waiting for a timer
+/// to be created) can still notice a dead peer instead of spinning forever.
+fn peer_disconnected(stream: &UnixStream) -> bool {
+    let mut buf = [0u8; 1];
+    stream.set_nonblocking(true).ok();
+    let result = stream.peek(&mut buf);
+    stream.set_nonblocking(false).ok();
+
+    match result {
+        Ok(0) => true,
+        Ok(_) => false,
+        Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => false,
+        Err(_) => true,
+    }
+}
+
+/// Push tick updates for a timer to a subscribed client until it's removed or disconnects.
+///
+/// If `keep_going` is set and `name` doesn't exist yet, keeps polling until it's created instead
+/// of erroring out immediately, mirroring `tail --keep-going`'s file-based behaviour. While
+/// waiting, also polls for the peer disconnecting (e.g. the client was killed before `name` was
+/// ever created), since nothing is written to the stream yet for a failed write to catch that.
+fn subscribe(mut stream: &UnixStream, state: &Arc<Mutex<Timers>>, name: &str, keep_going: bool) {
+    // Determine once whether `name` names a pomodoro or a timer, so a name that never existed can
+    // be reported distinctly from one that existed and was later removed.
+    enum Kind {
+        Timer,
+        Pomodoro,
+    }
+
+    let resolve_kind = || {
+        let timers = state.lock().unwrap();
+        if timers.pomodoros.contains_key(name) {
+            Some(Kind::Pomodoro)
+        } else if timers.timers.contains_key(name) {
+            Some(Kind::Timer)
+        } else {
+            None
+        }
+    };
+
+    let kind = loop {
+        match resolve_kind() {
+            Some(kind) => break kind,
+            None if keep_going => {
+                if peer_disconnected(stream) {
+                    return;
+                }
+                thread::sleep(TICK_INTERVAL);
+            }
+            None => {
+                let _ = serde_cbor::to_writer(
+                    &mut stream,
+                    &Answer::Error(format!("no timer named '{}'", name)),
+                );
+                return;
+            }
+        }
+    };
+
+    loop {
+        let answer = {
+            let timers = state.lock().unwrap();
+            match kind {
+                Kind::Pomodoro => timers
+                    .pomodoros
+                    .get(name)
+                    .map(|pomodoro| Answer::PomodoroTick(pomodoro.clone())),
+                Kind::Timer => timers
+                    .timers
+                    .get(name)
+                    .map(|timer| Answer::Tick(timer.clone())),
+            }
+            .unwrap_or(Answer::Removed)
+        };
+
+        let finished = matches!(answer, Answer::Removed);
+        if serde_cbor::to_writer(&mut stream, &answer).is_err() || finished {
+            return;
+        }
+
+        thread::sleep(TICK_INTERVAL);
+    }
+}
+
+/// Send a desktop notification that a countdown timer finished.
+fn notify_finished(name: &str, timer: &Timer) {
+    let duration = timer
+        .target
+        .map(|target| format_duration(target).to_string())
+        .unwrap_or_else(|| timer.format_elapsed());
+
+    if let Err(err) = Notification::new()
+        .summary(name)
+        .body(&format!("finished after {}", duration))
+        .show()
+    {
+        eprintln!("error: failed to send notification: {}", err);
+    }
+}
+
+/// Validate that a parsed duration actually fits in a `chrono::Duration`, exiting with an error
+/// otherwise.
+///
+/// `humantime::parse_duration` happily parses durations (e.g. `300000000y`) that overflow
+/// `chrono::Duration`'s representable range, which is used throughout for countdown math.
+fn validate_target_duration(duration: std::time::Duration) -> std::time::Duration {
+    if Duration::from_std(duration).is_err() {
+        eprintln!("error: duration '{}' is too large", format_duration(duration));
+        process::exit(1);
+    }
+    duration
+}
+
 /// Create and start new timer.
+///
+/// `peperone new 25m` only supplies one positional, which clap assigns to `NAME` regardless of
+/// its `default_value` — positionals fill left-to-right, so `DURATION` is left unset rather than
+/// `NAME` falling back to its default. If that lone value parses as a duration, treat it as
+/// `DURATION` for the default-named timer instead of silently creating an open-ended timer
+/// literally named "25m".
 fn new(matcher: &ArgMatches, timers: &mut Timers) {
-    let name = matcher.value_of("NAME").unwrap();
-    timers.timers.insert(name.into(), Timer::new());
+    let (name, duration) = match (matcher.value_of("NAME"), matcher.value_of("DURATION")) {
+        (Some(name), None) if parse_duration(name).is_ok() => (NAME_DEFAULT, Some(name)),
+        (name, duration) => (name.unwrap(), duration),
+    };
+
+    let target = duration.map(|duration| {
+        let duration = parse_duration(duration).unwrap_or_else(|err| {
+            eprintln!("error: invalid duration '{}': {}", duration, err);
+            process::exit(1);
+        });
+        validate_target_duration(duration)
+    });
+
+    if let Some(answer) = daemon_request(&Command::New {
+        name: name.into(),
+        target,
+    }) {
+        if let Answer::Error(err) = answer {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if timers.pomodoros.contains_key(name) {
+        eprintln!("error: a pomodoro named '{}' already exists", name);
+        process::exit(1);
+    }
+
+    timers.timers.insert(name.into(), Timer::new(target));
     timers.save();
 }
 
 /// Start existing timer.
 fn start(matcher: &ArgMatches, timers: &mut Timers) {
     let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::Start { name: name.into() }) {
+        exit_on_daemon_error("timer", name, answer);
+        return;
+    }
+
     match timers.timers.get_mut(name) {
         Some(timer) => timer.start(),
         None => {
@@ -267,6 +1068,12 @@ fn start(matcher: &ArgMatches, timers: &mut Timers) {
 /// Stop/pause existing timer.
 fn stop(matcher: &ArgMatches, timers: &mut Timers) {
     let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::Stop { name: name.into() }) {
+        exit_on_daemon_error("timer", name, answer);
+        return;
+    }
+
     match timers.timers.get_mut(name) {
         Some(timer) => timer.stop(),
         None => {
@@ -280,6 +1087,12 @@ fn stop(matcher: &ArgMatches, timers: &mut Timers) {
 /// Toggle existing timer.
 fn toggle(matcher: &ArgMatches, timers: &mut Timers) {
     let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::Toggle { name: name.into() }) {
+        exit_on_daemon_error("timer", name, answer);
+        return;
+    }
+
     match timers.timers.get_mut(name) {
         Some(timer) if timer.running() => timer.stop(),
         Some(timer) => timer.start(),
@@ -294,6 +1107,12 @@ fn toggle(matcher: &ArgMatches, timers: &mut Timers) {
 /// Remove a timer.
 fn remove(matcher: &ArgMatches, timers: &mut Timers) {
     let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::Remove { name: name.into() }) {
+        exit_on_daemon_error("timer", name, answer);
+        return;
+    }
+
     if timers.timers.remove(name).is_none() {
         eprintln!("error: no timer named '{}'", name);
         process::exit(1);
@@ -301,18 +1120,150 @@ fn remove(matcher: &ArgMatches, timers: &mut Timers) {
     timers.save();
 }
 
-/// List all timers.
-fn list(_matcher: &ArgMatches, timers: &mut Timers) {
-    for name in timers.timers.keys() {
-        println!("{}", name);
+/// Print a single timer, honouring `--template`/`--format`.
+fn print_timer(name: &str, timer: &Timer, matcher: &ArgMatches) {
+    if let Some(template) = matcher.value_of("template") {
+        println!("{}", render_template(template, &timer.info(name)));
+    } else if matcher.value_of("format") == Some("json") {
+        println!(
+            "{}",
+            serde_json::to_string(&timer.info(name)).expect("failed to serialize timer"),
+        );
+    } else {
+        println!("{}", timer.format_display());
+    }
+}
+
+/// Print a single pomodoro, honouring `--template`/`--format`.
+fn print_pomodoro(name: &str, pomodoro: &Pomodoro, matcher: &ArgMatches) {
+    if let Some(template) = matcher.value_of("template") {
+        println!("{}", render_pomodoro_template(template, &pomodoro.info(name)));
+    } else if matcher.value_of("format") == Some("json") {
+        println!(
+            "{}",
+            serde_json::to_string(&pomodoro.info(name)).expect("failed to serialize pomodoro"),
+        );
+    } else {
+        println!("{}: {}", pomodoro.phase.label(), pomodoro.timer.format_display());
+    }
+}
+
+/// Print a list of timers and pomodoros, honouring `--template`/`--format`/`--json`.
+///
+/// `json` is computed by the caller rather than probed for here, since only `list`'s matcher
+/// registers a `json` arg; `show --all` reuses this with `show`'s matcher, which doesn't.
+fn print_entries(
+    timers: &[(String, Timer)],
+    pomodoros: &[(String, Pomodoro)],
+    matcher: &ArgMatches,
+    json: bool,
+) {
+    if let Some(template) = matcher.value_of("template") {
+        for (name, timer) in timers {
+            println!("{}", render_template(template, &timer.info(name)));
+        }
+        for (name, pomodoro) in pomodoros {
+            println!("{}", render_pomodoro_template(template, &pomodoro.info(name)));
+        }
+        return;
+    }
+
+    if json {
+        let mut infos: Vec<serde_json::Value> = timers
+            .iter()
+            .map(|(name, timer)| {
+                serde_json::to_value(timer.info(name)).expect("failed to serialize timer")
+            })
+            .collect();
+        infos.extend(pomodoros.iter().map(|(name, pomodoro)| {
+            serde_json::to_value(pomodoro.info(name)).expect("failed to serialize pomodoro")
+        }));
+        println!(
+            "{}",
+            serde_json::to_string(&infos).expect("failed to serialize timers"),
+        );
+        return;
+    }
+
+    for (name, timer) in timers {
+        println!("{}\t{}", name, timer.format_display());
+    }
+    for (name, pomodoro) in pomodoros {
+        println!(
+            "{}\t{}: {}",
+            name,
+            pomodoro.phase.label(),
+            pomodoro.timer.format_display()
+        );
     }
 }
 
+/// List all timers.
+fn list(matcher: &ArgMatches, timers: &mut Timers) {
+    let json = matcher.is_present("json") || matcher.value_of("format") == Some("json");
+    list_entries(matcher, timers, json);
+}
+
+/// Fetch and print every timer and pomodoro, honouring `--template`/`--format`/`--json`.
+///
+/// `json` is computed by the caller: `list`'s own matcher registers a `json` arg, but `show
+/// --all` reuses this with `show`'s matcher, which doesn't.
+fn list_entries(matcher: &ArgMatches, timers: &mut Timers, json: bool) {
+    let (timer_entries, pomodoro_entries) = match daemon_request(&Command::List) {
+        Some(Answer::List(timers, pomodoros)) => (timers, pomodoros),
+        _ => (
+            timers
+                .timers
+                .iter()
+                .map(|(name, timer)| (name.clone(), timer.clone()))
+                .collect(),
+            timers
+                .pomodoros
+                .iter()
+                .map(|(name, pomodoro)| (name.clone(), pomodoro.clone()))
+                .collect(),
+        ),
+    };
+
+    print_entries(&timer_entries, &pomodoro_entries, matcher, json);
+}
+
 /// Show a timer.
+///
+/// No `--notify` here: unlike `tail`, each invocation is a fresh, stateless process with nothing
+/// to track whether it already notified for the current countdown, so a finished timer would
+/// re-notify on every single call. `tail --notify` already covers the alarm use case.
 fn show(matcher: &ArgMatches, timers: &mut Timers) {
+    if matcher.is_present("all") {
+        let json = matcher.value_of("format") == Some("json");
+        list_entries(matcher, timers, json);
+        return;
+    }
+
     let name = matcher.value_of("NAME").unwrap();
     let quiet = matcher.is_present("quiet");
 
+    // Prefer the daemon, since it may hold state our local, possibly-stale `timers` never learned
+    // about.
+    if let Some(answer) = daemon_request(&Command::Show { name: name.into() }) {
+        match answer {
+            Answer::Pomodoro(pomodoro) => print_pomodoro(name, &pomodoro, matcher),
+            Answer::Timer(timer) => print_timer(name, &timer, matcher),
+            _ => {
+                if !quiet {
+                    eprintln!("error: no timer named '{}'", name);
+                }
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(pomodoro) = timers.pomodoros.get(name) {
+        print_pomodoro(name, pomodoro, matcher);
+        return;
+    }
+
     let timer = match timers.timers.get(name) {
         Some(timer) => timer,
         None => {
@@ -322,14 +1273,36 @@ fn show(matcher: &ArgMatches, timers: &mut Timers) {
             process::exit(1);
         }
     };
-    println!("{}", timer.format_elapsed());
+
+    print_timer(name, timer, matcher);
 }
 
-/// Tail a timer.
+/// Tail a timer or pomodoro.
 fn tail(matcher: &ArgMatches, timers: &mut Timers) {
     let name = matcher.value_of("NAME").unwrap();
+
+    // Prefer the daemon, since it may hold state our local, possibly-stale `timers` never learned
+    // about (e.g. a pomodoro started through the daemon after `timers` was loaded).
+    if let Some(stream) = daemon_connect() {
+        tail_daemon(matcher, stream);
+        return;
+    }
+
+    if timers.pomodoros.contains_key(name) {
+        tail_pomodoro(matcher, timers);
+        return;
+    }
+
+    tail_timer(matcher, timers);
+}
+
+/// Tail a timer, handing off to [`tail_pomodoro`] if a pomodoro under the same name shows up
+/// while waiting (see the recheck below).
+fn tail_timer(matcher: &ArgMatches, timers: &mut Timers) {
+    let name = matcher.value_of("NAME").unwrap();
     let keep_going = matcher.is_present("keep-going");
     let quiet = matcher.is_present("quiet");
+    let notify = matcher.is_present("notify") && !matcher.is_present("no-notify");
 
     // Load timer
     let mut timer = timers.timers.get(name);
@@ -340,6 +1313,9 @@ fn tail(matcher: &ArgMatches, timers: &mut Timers) {
         process::exit(1);
     }
 
+    // Whether we already notified for the current countdown, so we don't repeat ourselves
+    let mut notified = false;
+
     // Create timer file watcher
     let (tx, rx) = channel();
     let mut watcher = watcher(tx, Duration::milliseconds(100).to_std().unwrap()).unwrap();
@@ -348,9 +1324,15 @@ fn tail(matcher: &ArgMatches, timers: &mut Timers) {
         .unwrap();
 
     loop {
-        // Print time if running
+        // Print time if running, notify once when a countdown timer just finished
         match timer {
-            Some(timer) if timer.running() => println!("{}", timer.format_elapsed()),
+            Some(timer) if timer.running() => {
+                if notify && timer.finished() && !notified {
+                    notify_finished(name, timer);
+                    notified = true;
+                }
+                println!("{}", timer.format_display());
+            }
             None => println!("0:00"),
             _ => {}
         }
@@ -383,14 +1365,533 @@ fn tail(matcher: &ArgMatches, timers: &mut Timers) {
                 rx.try_iter().count();
 
                 *timers = Timers::load();
+
+                // A pomodoro may have been created under `name` while we were waiting for a
+                // plain timer to appear (e.g. `tail --keep-going foo` started before `foo`
+                // existed, followed by `pomodoro start foo`); hand off instead of looping on
+                // `0:00` forever.
+                if timers.pomodoros.contains_key(name) {
+                    return tail_pomodoro(matcher, timers);
+                }
+
                 timer = timers.timers.get(name);
                 if timer.is_none() && !keep_going {
                     process::exit(0);
                 }
 
+                // Reset the notified flag if the timer was restarted
+                if !timer.map(Timer::finished).unwrap_or(false) {
+                    notified = false;
+                }
+
                 // TODO: only continue if timer state changed
                 break;
             }
         }
     }
 }
+
+/// Tail a timer by subscribing to push updates from the daemon.
+fn tail_daemon(matcher: &ArgMatches, stream: UnixStream) {
+    let name = matcher.value_of("NAME").unwrap();
+    let keep_going = matcher.is_present("keep-going");
+    let quiet = matcher.is_present("quiet");
+    let notify = matcher.is_present("notify") && !matcher.is_present("no-notify");
+
+    serde_cbor::to_writer(
+        &stream,
+        &Command::Subscribe {
+            name: name.into(),
+            keep_going,
+        },
+    )
+    .expect("failed to subscribe to daemon");
+
+    let mut notified = false;
+    let mut last_phase = None;
+    for answer in serde_cbor::Deserializer::from_reader(&stream).into_iter::<Answer>() {
+        match answer.expect("failed to read update from daemon") {
+            Answer::Tick(timer) => {
+                if notify && timer.finished() && !notified {
+                    notify_finished(name, &timer);
+                    notified = true;
+                } else if !timer.finished() {
+                    notified = false;
+                }
+
+                if timer.running() {
+                    println!("{}", timer.format_display());
+                } else {
+                    println!("0:00");
+                }
+            }
+            Answer::PomodoroTick(pomodoro) => {
+                if let Some(last_phase) = last_phase.replace(pomodoro.phase) {
+                    if notify && last_phase != pomodoro.phase {
+                        notify_phase_change(name, &pomodoro);
+                    }
+                }
+
+                if pomodoro.timer.running() {
+                    println!("{}: {}", pomodoro.phase.label(), pomodoro.timer.format_display());
+                }
+            }
+            Answer::Removed => {
+                if !keep_going {
+                    process::exit(0);
+                }
+            }
+            Answer::Error(_) => {
+                if !keep_going {
+                    if !quiet {
+                        eprintln!("error: no timer named '{}'", name);
+                    }
+                    process::exit(1);
+                }
+            }
+            Answer::Ok | Answer::List(_, _) | Answer::Timer(_) | Answer::Pomodoro(_) => {}
+        }
+    }
+}
+
+/// Tail a pomodoro, advancing it to the next phase whenever the current one finishes.
+fn tail_pomodoro(matcher: &ArgMatches, timers: &mut Timers) {
+    let name = matcher.value_of("NAME").unwrap();
+    let quiet = matcher.is_present("quiet");
+    let notify = matcher.is_present("notify") && !matcher.is_present("no-notify");
+
+    if !timers.pomodoros.contains_key(name) {
+        if !quiet {
+            eprintln!("error: no pomodoro named '{}'", name);
+        }
+        process::exit(1);
+    }
+
+    // Create timer file watcher
+    let (tx, rx) = channel();
+    let mut watcher = watcher(tx, Duration::milliseconds(100).to_std().unwrap()).unwrap();
+    watcher
+        .watch(timers_path(), RecursiveMode::NonRecursive)
+        .unwrap();
+
+    loop {
+        let pomodoro = match timers.pomodoros.get(name) {
+            Some(pomodoro) => pomodoro,
+            None => process::exit(0),
+        };
+
+        // Print time if running
+        if pomodoro.timer.running() {
+            println!("{}: {}", pomodoro.phase.label(), pomodoro.timer.format_display());
+        }
+
+        // Advance to the next phase once the current one finishes
+        if pomodoro.timer.running() && pomodoro.timer.finished() {
+            let pomodoro = timers.pomodoros.get_mut(name).unwrap();
+            pomodoro.advance();
+            if notify {
+                notify_phase_change(name, pomodoro);
+            }
+            timers.save();
+        }
+
+        let pomodoro = timers.pomodoros.get(name).unwrap();
+
+        // Determine expected tick length
+        let delay = if pomodoro.timer.running() {
+            std::time::Duration::from_millis(
+                (1000 - pomodoro.timer.elapsed().num_milliseconds() % 1000) as u64,
+            )
+        } else {
+            std::time::Duration::from_secs(9999999999)
+        };
+
+        // While waiting for next tick, process file events
+        while let Ok(event) = rx.recv_timeout(delay) {
+            let recheck = match event {
+                DebouncedEvent::NoticeWrite(_) => false,
+                DebouncedEvent::NoticeRemove(_) => false,
+                DebouncedEvent::Create(_) => true,
+                DebouncedEvent::Write(_) => true,
+                DebouncedEvent::Chmod(_) => false,
+                DebouncedEvent::Remove(_) => true,
+                DebouncedEvent::Rename(_, _) => true,
+                DebouncedEvent::Rescan => true,
+                DebouncedEvent::Error(_, _) => true,
+            };
+
+            // Recheck pomodoro, make sure it's still active
+            if recheck {
+                // Drain remaining events
+                rx.try_iter().count();
+
+                *timers = Timers::load();
+                if !timers.pomodoros.contains_key(name) {
+                    process::exit(0);
+                }
+
+                // TODO: only continue if pomodoro state changed
+                break;
+            }
+        }
+    }
+}
+
+/// Send a desktop notification for a pomodoro phase change.
+fn notify_phase_change(name: &str, pomodoro: &Pomodoro) {
+    let body = match pomodoro.phase {
+        Phase::Work => "Back to work!",
+        Phase::ShortBreak | Phase::LongBreak => "Break time!",
+    };
+
+    if let Err(err) = Notification::new().summary(name).body(body).show() {
+        eprintln!("error: failed to send notification: {}", err);
+    }
+}
+
+/// Manage pomodoros.
+fn pomodoro(matcher: &ArgMatches, timers: &mut Timers) {
+    if let Some(matcher) = matcher.subcommand_matches("start") {
+        pomodoro_start(matcher, timers);
+    } else if let Some(matcher) = matcher.subcommand_matches("toggle") {
+        pomodoro_toggle(matcher, timers);
+    } else if let Some(matcher) = matcher.subcommand_matches("stop") {
+        pomodoro_stop(matcher, timers);
+    } else {
+        unreachable!()
+    }
+}
+
+/// Create and start a new pomodoro.
+fn pomodoro_start(matcher: &ArgMatches, timers: &mut Timers) {
+    let name = matcher.value_of("NAME").unwrap();
+    let work = parse_pomodoro_duration(matcher, "WORK");
+    let short_break = parse_pomodoro_duration(matcher, "SHORT");
+    let long_break = parse_pomodoro_duration(matcher, "LONG");
+    let count: u32 = matcher.value_of("COUNT").unwrap().parse().unwrap_or_else(|_| {
+        eprintln!("error: invalid work interval count");
+        process::exit(1);
+    });
+
+    if let Some(answer) = daemon_request(&Command::PomodoroStart {
+        name: name.into(),
+        work,
+        short_break,
+        long_break,
+        count,
+    }) {
+        if let Answer::Error(err) = answer {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if timers.timers.contains_key(name) {
+        eprintln!("error: a timer named '{}' already exists", name);
+        process::exit(1);
+    }
+
+    timers.pomodoros.insert(
+        name.into(),
+        Pomodoro::new(work, short_break, long_break, count),
+    );
+    timers.save();
+}
+
+/// Parse a duration argument for the `pomodoro start` subcommand.
+fn parse_pomodoro_duration(matcher: &ArgMatches, arg: &str) -> std::time::Duration {
+    let value = matcher.value_of(arg).unwrap();
+    let duration = parse_duration(value).unwrap_or_else(|err| {
+        eprintln!("error: invalid duration '{}': {}", value, err);
+        process::exit(1);
+    });
+    validate_target_duration(duration)
+}
+
+/// Toggle an existing pomodoro (start/stop).
+fn pomodoro_toggle(matcher: &ArgMatches, timers: &mut Timers) {
+    let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::PomodoroToggle { name: name.into() }) {
+        exit_on_daemon_error("pomodoro", name, answer);
+        return;
+    }
+
+    match timers.pomodoros.get_mut(name) {
+        Some(pomodoro) if pomodoro.timer.running() => pomodoro.timer.stop(),
+        Some(pomodoro) => pomodoro.timer.start(),
+        None => {
+            eprintln!("error: no pomodoro named '{}'", name);
+            process::exit(1);
+        }
+    }
+    timers.save();
+}
+
+/// Stop and remove a pomodoro.
+fn pomodoro_stop(matcher: &ArgMatches, timers: &mut Timers) {
+    let name = matcher.value_of("NAME").unwrap();
+
+    if let Some(answer) = daemon_request(&Command::PomodoroStop { name: name.into() }) {
+        exit_on_daemon_error("pomodoro", name, answer);
+        return;
+    }
+
+    if timers.pomodoros.remove(name).is_none() {
+        eprintln!("error: no pomodoro named '{}'", name);
+        process::exit(1);
+    }
+    timers.save();
+}
+
+#[cfg(test)]
+mod daemon_tests {
+    use super::*;
+
+    fn state() -> Arc<Mutex<Timers>> {
+        Arc::new(Mutex::new(Timers::default()))
+    }
+
+    #[test]
+    fn apply_command_new_then_show_roundtrips_the_timer() {
+        let state = state();
+        assert!(matches!(
+            apply_command(
+                &state,
+                Command::New {
+                    name: "work".into(),
+                    target: None,
+                },
+            ),
+            Answer::Ok
+        ));
+
+        match apply_command(
+            &state,
+            Command::Show {
+                name: "work".into(),
+            },
+        ) {
+            Answer::Timer(_) => {}
+            other => panic!("expected Answer::Timer, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn apply_command_rejects_a_timer_under_a_name_already_taken_by_a_pomodoro() {
+        let state = state();
+        apply_command(
+            &state,
+            Command::PomodoroStart {
+                name: "focus".into(),
+                work: std::time::Duration::from_secs(1),
+                short_break: std::time::Duration::from_secs(1),
+                long_break: std::time::Duration::from_secs(1),
+                count: 1,
+            },
+        );
+
+        let answer = apply_command(
+            &state,
+            Command::New {
+                name: "focus".into(),
+                target: None,
+            },
+        );
+        assert!(matches!(answer, Answer::Error(_)));
+    }
+
+    #[test]
+    fn apply_command_show_of_unknown_name_errors() {
+        let state = state();
+        let answer = apply_command(
+            &state,
+            Command::Show {
+                name: "ghost".into(),
+            },
+        );
+        assert!(matches!(answer, Answer::Error(_)));
+    }
+
+    #[test]
+    fn handle_daemon_connection_serves_new_and_show_over_the_wire() {
+        let state = state();
+        let (client, server) = UnixStream::pair().expect("failed to create socket pair");
+        let worker = thread::spawn(move || handle_daemon_connection(server, state));
+
+        serde_cbor::to_writer(
+            &client,
+            &Command::New {
+                name: "work".into(),
+                target: None,
+            },
+        )
+        .unwrap();
+        let answer = Answer::deserialize(&mut serde_cbor::Deserializer::from_reader(&client)).unwrap();
+        assert!(matches!(answer, Answer::Ok));
+
+        serde_cbor::to_writer(
+            &client,
+            &Command::Show {
+                name: "work".into(),
+            },
+        )
+        .unwrap();
+        let answer = Answer::deserialize(&mut serde_cbor::Deserializer::from_reader(&client)).unwrap();
+        assert!(matches!(answer, Answer::Timer(_)));
+
+        drop(client);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn subscribe_pushes_removed_once_the_timer_is_gone() {
+        let state = state();
+        state
+            .lock()
+            .unwrap()
+            .timers
+            .insert("work".into(), Timer::new(None));
+
+        let (client, server) = UnixStream::pair().expect("failed to create socket pair");
+        let worker_state = Arc::clone(&state);
+        let worker = thread::spawn(move || subscribe(&server, &worker_state, "work", false));
+
+        let mut answers = serde_cbor::Deserializer::from_reader(&client);
+        let answer = Answer::deserialize(&mut answers).unwrap();
+        assert!(matches!(answer, Answer::Tick(_)));
+
+        state.lock().unwrap().timers.remove("work");
+
+        loop {
+            match Answer::deserialize(&mut answers) {
+                Ok(Answer::Removed) => break,
+                Ok(_) => continue,
+                Err(err) => panic!("subscription ended without Answer::Removed: {}", err),
+            }
+        }
+
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn subscribe_to_an_unknown_name_errors_immediately() {
+        let state = state();
+        let (client, server) = UnixStream::pair().expect("failed to create socket pair");
+        let worker = thread::spawn(move || subscribe(&server, &state, "ghost", false));
+
+        let answer: Answer = serde_cbor::from_reader(&client).unwrap();
+        assert!(matches!(answer, Answer::Error(_)));
+
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn subscribe_keep_going_stops_waiting_once_the_peer_disconnects() {
+        let state = state();
+        let (client, server) = UnixStream::pair().expect("failed to create socket pair");
+        let worker = thread::spawn(move || subscribe(&server, &state, "ghost", true));
+
+        drop(client);
+
+        // The worker must notice the disconnect and return on its own; if it didn't, this would
+        // hang forever instead of joining.
+        worker.join().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod timer_tests {
+    use super::*;
+
+    /// A stopped timer with the given elapsed/target durations, so `remaining`/`finished` are
+    /// deterministic instead of racing `Utc::now()`.
+    fn stopped(elapsed: std::time::Duration, target: std::time::Duration) -> Timer {
+        Timer {
+            start: None,
+            offset: elapsed,
+            target: Some(target),
+        }
+    }
+
+    #[test]
+    fn remaining_is_zero_and_finished_when_elapsed_equals_target() {
+        let timer = stopped(
+            std::time::Duration::from_secs(60),
+            std::time::Duration::from_secs(60),
+        );
+        assert_eq!(timer.remaining(), Some(Duration::zero()));
+        assert!(timer.finished());
+    }
+
+    #[test]
+    fn remaining_is_clamped_to_zero_when_elapsed_exceeds_target() {
+        let timer = stopped(
+            std::time::Duration::from_secs(90),
+            std::time::Duration::from_secs(60),
+        );
+        assert_eq!(timer.remaining(), Some(Duration::zero()));
+        assert!(timer.finished());
+    }
+
+    #[test]
+    fn remaining_is_positive_and_not_finished_before_target() {
+        let timer = stopped(
+            std::time::Duration::from_secs(30),
+            std::time::Duration::from_secs(60),
+        );
+        assert_eq!(timer.remaining(), Some(Duration::seconds(30)));
+        assert!(!timer.finished());
+    }
+}
+
+#[cfg(test)]
+mod pomodoro_tests {
+    use super::*;
+
+    fn pomodoro(count: u32) -> Pomodoro {
+        Pomodoro::new(
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(1),
+            count,
+        )
+    }
+
+    #[test]
+    fn advance_takes_a_short_break_between_work_intervals() {
+        let mut pomodoro = pomodoro(2);
+        assert_eq!(pomodoro.phase, Phase::Work);
+        assert_eq!(pomodoro.cycle, 0);
+
+        pomodoro.advance();
+        assert_eq!(pomodoro.phase, Phase::ShortBreak);
+        assert_eq!(pomodoro.cycle, 1);
+    }
+
+    #[test]
+    fn advance_takes_a_long_break_and_resets_the_cycle_after_the_last_work_interval() {
+        let mut pomodoro = pomodoro(2);
+        pomodoro.advance(); // Work -> ShortBreak, cycle 1
+        pomodoro.cycle = 1;
+        pomodoro.phase = Phase::Work;
+
+        pomodoro.advance();
+        assert_eq!(pomodoro.phase, Phase::LongBreak);
+        assert_eq!(pomodoro.cycle, 0);
+    }
+
+    #[test]
+    fn advance_always_returns_to_work_after_a_break() {
+        let mut short_break = pomodoro(2);
+        short_break.phase = Phase::ShortBreak;
+        short_break.advance();
+        assert_eq!(short_break.phase, Phase::Work);
+
+        let mut long_break = pomodoro(2);
+        long_break.phase = Phase::LongBreak;
+        long_break.advance();
+        assert_eq!(long_break.phase, Phase::Work);
+    }
+}